@@ -0,0 +1,263 @@
+//! A frontend for animated still-image formats (GIF, WebP, APNG).
+//!
+//! Unlike a video, these formats decode up front into a small, finite sequence of frames, each
+//! carrying its own display delay. This frontend pulls that sequence through the `image` crate's
+//! `AnimationDecoder`, feeds every composited frame into the existing `ImagePipeline`, and drives
+//! playback from the decoded delays instead of a fixed framerate. The file's own loop count is not
+//! read: playback loops indefinitely unless a count is given with `set_loop_count`.
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, Frame, ImageResult, RgbaImage};
+
+use crate::common::errors::*;
+use crate::pipeline::char_maps::CharMap;
+use crate::pipeline::image_pipeline::ImagePipeline;
+
+/// Which animated container a frame sequence was decoded from.
+///
+/// Only used to pick the right `image` decoder; once the frames are decoded, playback is
+/// format-agnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimatedFormat {
+    Gif,
+    WebP,
+    Apng,
+}
+
+/// How many times an animation should play before stopping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopCount {
+    Infinite,
+    Times(u16),
+}
+
+/// Decodes every frame of an animated image up front, pairing each with its delay so playback
+/// timing follows the file rather than a fixed framerate.
+pub struct AnimatedImageFrontend<T: CharMap> {
+    pipeline: ImagePipeline<T>,
+    frames: Vec<Frame>,
+    loop_count: LoopCount,
+}
+
+impl<T: CharMap> AnimatedImageFrontend<T> {
+    /// Decodes every frame of `reader` as `format`, ready to be played back through `pipeline`.
+    ///
+    /// Each yielded `Frame` is already fully composited by the underlying `image` decoder (it
+    /// resolves disposal methods and partial updates internally), so the grayscale and RGB
+    /// buffers handed to the pipeline are always the complete frame, never a delta.
+    ///
+    /// If `lossy` is set, a frame that fails to decode does not abort playback outright: it is
+    /// logged and replaced with a blank buffer the size of the previous frame (or dropped if no
+    /// frame precedes it), mirroring `image`'s own `load_lossy` recovery. This is a best-effort recovery of the frames decoded so far, not a full "skip
+    /// the bad frame and keep going": the underlying GIF/WebP/APNG decoders never advance their
+    /// read position past a decode error, so any frames after the first error are unreachable and
+    /// are simply not included. With `lossy` unset, the first decode error is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MyError` if the container fails to open, or (when `lossy` is unset) if any
+    /// frame fails to decode.
+    pub fn new<R: Read>(
+        reader: R,
+        format: AnimatedFormat,
+        pipeline: ImagePipeline<T>,
+        lossy: bool,
+    ) -> Result<Self, MyError> {
+        let frames = match format {
+            AnimatedFormat::Gif => {
+                let frames = GifDecoder::new(reader)
+                    .map_err(|err| MyError::Pipeline(format!("{ERROR_DECODE}:{err:?}")))?
+                    .into_frames();
+                Self::collect_frames(frames, lossy)?
+            }
+            AnimatedFormat::WebP => {
+                let frames = WebPDecoder::new(reader)
+                    .map_err(|err| MyError::Pipeline(format!("{ERROR_DECODE}:{err:?}")))?
+                    .into_frames();
+                Self::collect_frames(frames, lossy)?
+            }
+            AnimatedFormat::Apng => {
+                let frames = PngDecoder::new(reader)
+                    .map_err(|err| MyError::Pipeline(format!("{ERROR_DECODE}:{err:?}")))?
+                    .apng()
+                    .into_frames();
+                Self::collect_frames(frames, lossy)?
+            }
+        };
+
+        Ok(Self {
+            pipeline,
+            frames,
+            loop_count: LoopCount::Infinite,
+        })
+    }
+
+    /// Drains a decoder's `Frames` iterator into a `Vec<Frame>`.
+    ///
+    /// In strict mode this is equivalent to `Frames::collect_frames`. In lossy mode, a frame
+    /// that errors out partway through decoding is replaced by a blank frame the size of the
+    /// last successfully decoded one (or dropped if no prior frame exists to size it from), the
+    /// error is logged rather than propagated, and decoding stops there: the underlying decoders
+    /// don't advance their read position past a bad frame, so calling `next()` again would just
+    /// re-hit the same error forever rather than reach the frames that follow it.
+    fn collect_frames(
+        frames: impl Iterator<Item = ImageResult<Frame>>,
+        lossy: bool,
+    ) -> Result<Vec<Frame>, MyError> {
+        if !lossy {
+            return frames
+                .collect::<ImageResult<Vec<Frame>>>()
+                .map_err(|err| MyError::Pipeline(format!("{ERROR_DECODE}:{err:?}")));
+        }
+
+        let mut out = Vec::new();
+        let mut last_dimensions = None;
+
+        for result in frames {
+            match result {
+                Ok(frame) => {
+                    let buffer = frame.buffer();
+                    last_dimensions = Some((buffer.width(), buffer.height()));
+                    out.push(frame);
+                }
+                Err(err) => {
+                    log::error!("tplay: dropping corrupt animation frame: {err}");
+                    if let Some((width, height)) = last_dimensions {
+                        out.push(Frame::new(RgbaImage::new(width, height)));
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Sets how many times the animation should loop and returns a mutable reference to self.
+    pub fn set_loop_count(&mut self, loop_count: LoopCount) -> &mut Self {
+        self.loop_count = loop_count;
+        self
+    }
+
+    /// Renders every decoded frame through the pipeline, returning each frame's ASCII art
+    /// alongside its display delay.
+    ///
+    /// This does not itself sleep between frames; the caller drives playback (and any
+    /// looping, per `loop_count`) using the returned delays, the same way the rest of the
+    /// render loop paces video frames.
+    pub fn render_frames(&self) -> Result<Vec<(Vec<String>, Duration)>, MyError> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let delay: Duration = frame.delay().into();
+                let img = image::DynamicImage::ImageRgba8(frame.buffer().to_owned());
+                let (gray, color) = self.pipeline.resize(&img)?;
+                Ok((self.pipeline.to_ascii(&gray, &color), delay))
+            })
+            .collect()
+    }
+
+    /// Whether the animation should keep looping given how many times it has already played.
+    pub fn should_loop(&self, plays_so_far: u16) -> bool {
+        match self.loop_count {
+            LoopCount::Infinite => true,
+            LoopCount::Times(n) => plays_so_far < n,
+        }
+    }
+}
+
+/// Blocks the calling thread for `delay`, pacing animated playback between frames the same way
+/// the video frontend paces video frames.
+pub fn sleep_for_delay(delay: Duration) {
+    thread::sleep(delay);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Delay;
+    use std::io;
+
+    fn solid_frame(width: u32, height: u32) -> Frame {
+        Frame::new(RgbaImage::from_pixel(width, height, image::Rgba([1, 2, 3, 255])))
+    }
+
+    fn decode_error() -> ImageResult<Frame> {
+        Err(image::ImageError::IoError(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated")))
+    }
+
+    #[test]
+    fn test_collect_frames_lossy_stops_after_first_error() {
+        let frames = vec![Ok(solid_frame(2, 2)), decode_error(), Ok(solid_frame(2, 2))];
+
+        let out = AnimatedImageFrontend::<Vec<char>>::collect_frames(frames.into_iter(), true)
+            .expect("lossy mode never returns an error");
+
+        // The good frame, then one recovered blank frame sized from it, then nothing: the third
+        // `Ok` is never reached because collection stops at the first error.
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[1].buffer().dimensions(), (2, 2));
+        assert!(out[1].buffer().pixels().all(|p| *p == image::Rgba([0, 0, 0, 0])));
+    }
+
+    #[test]
+    fn test_collect_frames_lossy_drops_leading_error_with_no_prior_frame() {
+        let frames = vec![decode_error(), Ok(solid_frame(2, 2))];
+
+        let out = AnimatedImageFrontend::<Vec<char>>::collect_frames(frames.into_iter(), true)
+            .expect("lossy mode never returns an error");
+
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_collect_frames_strict_propagates_error() {
+        let frames = vec![Ok(solid_frame(2, 2)), decode_error()];
+
+        let result = AnimatedImageFrontend::<Vec<char>>::collect_frames(frames.into_iter(), false);
+        assert!(result.is_err());
+    }
+
+    fn frontend_with_frames(frames: Vec<Frame>, loop_count: LoopCount) -> AnimatedImageFrontend<Vec<char>> {
+        AnimatedImageFrontend {
+            pipeline: ImagePipeline::new((1, 1), vec![' ', '#'], false, false, false),
+            frames,
+            loop_count,
+        }
+    }
+
+    #[test]
+    fn test_should_loop_infinite_always_continues() {
+        let frontend = frontend_with_frames(vec![], LoopCount::Infinite);
+        assert!(frontend.should_loop(0));
+        assert!(frontend.should_loop(1000));
+    }
+
+    #[test]
+    fn test_should_loop_times_stops_after_n_plays() {
+        let frontend = frontend_with_frames(vec![], LoopCount::Times(3));
+        assert!(frontend.should_loop(0));
+        assert!(frontend.should_loop(2));
+        assert!(!frontend.should_loop(3));
+    }
+
+    #[test]
+    fn test_render_frames_passes_through_frame_delay() {
+        let frame = Frame::from_parts(
+            RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])),
+            0,
+            0,
+            Delay::from_numer_denom_ms(250, 1),
+        );
+        let frontend = frontend_with_frames(vec![frame], LoopCount::Infinite);
+
+        let output = frontend.render_frames().expect("render_frames should succeed");
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].1, Duration::from_millis(250));
+    }
+}
@@ -33,6 +33,36 @@ pub trait CharMap : Clone {
     fn get_line_prefix(&self) -> &str {
         ""
     }
+
+    /// Whether this char map wants the per-cell Sobel gradient magnitude and angle computed by
+    /// `ImagePipeline` and passed to `get_char_with_gradient`. Luminance-only char maps can
+    /// leave this as `false` and are never asked to pay for the extra computation.
+    fn needs_gradient(&self) -> bool {
+        false
+    }
+
+    /// Like `get_char`, but additionally given the cell's Sobel gradient `(magnitude, angle)`
+    /// (angle in radians), when `needs_gradient` returns `true`. The default implementation
+    /// ignores the gradient and defers to `get_char`.
+    fn get_char_with_gradient(&self, image: &SubImage<&GrayImage>, _gradient: (f32, f32)) -> char {
+        self.get_char(image)
+    }
+
+    /// Whether this char map thresholds each subpixel to black or white (one bit per dot), as
+    /// opposed to looking a luminance ramp up by magnitude. `ImagePipeline` only applies its
+    /// optional Floyd-Steinberg dithering pass ahead of char maps that report `true` here, since
+    /// dithering a ramp lookup would just scramble it.
+    fn is_bilevel(&self) -> bool {
+        false
+    }
+
+    /// Whether `c` (as returned by `get_char`/`get_char_with_gradient`) is this char map's
+    /// "blank" glyph — i.e. renders as empty space. `ImagePipeline` uses this to skip emitting a
+    /// color escape around blank cells. Defaults to plain ASCII space; char maps whose blank
+    /// glyph is some other code point (e.g. the empty braille pattern) override this.
+    fn is_blank(&self, c: char) -> bool {
+        c == ' '
+    }
 }
 
 impl CharMap for Vec<char> {
@@ -47,12 +77,13 @@ impl CharMap for Vec<char> {
     }
 }
 
+const BRAILLE_BLANK: u32 = 0x2800;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Braille;
 
 impl CharMap for Braille {
     fn get_char(&self, image: &SubImage<&GrayImage>) -> char {
-        const BRAILLE_BLANK: u32 = 0x2800;
         let mut braille = BRAILLE_BLANK;
 
         let braille_dots = [
@@ -78,6 +109,14 @@ impl CharMap for Braille {
     fn get_subpixels(&self) -> (u32, u32) {
         (2, 4)
     }
+
+    fn is_bilevel(&self) -> bool {
+        true
+    }
+
+    fn is_blank(&self, c: char) -> bool {
+        c as u32 == BRAILLE_BLANK
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -123,6 +162,10 @@ impl CharMap for Mosaic {
     fn get_subpixels(&self) -> (u32, u32) {
         (2, 3)
     }
+
+    fn is_bilevel(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -172,14 +215,83 @@ impl CharMap for TeletextMosaic {
     fn get_line_prefix(&self) -> &str {
         "\u{E017}"
     }
+
+    fn is_bilevel(&self) -> bool {
+        true
+    }
+
+    fn is_blank(&self, c: char) -> bool {
+        c == teletext_mosaic_char(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edges {
+    /// Fallback luminance ramp used for cells that aren't edges (or, in blend mode, that are
+    /// only weak edges).
+    pub ramp: Vec<char>,
+    /// Sobel gradient magnitude above which a cell is considered an edge.
+    pub threshold: f32,
+    /// When `true`, edges blend with the ramp instead of fully overriding it: only cells whose
+    /// gradient clears twice the threshold are drawn as directional glyphs, leaving moderate
+    /// edges to the ramp's shading. When `false`, any cell above `threshold` is drawn as a
+    /// directional glyph.
+    pub blend: bool,
+}
+
+/// Quantizes a Sobel *gradient* angle (radians, any range) into one of four undirected,
+/// 45°-wide orientation bins and returns the glyph for the *edge* running through that cell.
+///
+/// `θ = atan2(Gy, Gx)` points in the direction of steepest intensity change, which is
+/// perpendicular to the edge itself (e.g. a vertical edge has a horizontal, `θ ≈ 0` gradient).
+/// The edge runs along `θ + 90°`, so we rotate by a quarter turn before binning.
+fn direction_char(angle: f32) -> char {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, FRAC_PI_8, PI};
+
+    let angle = (angle + FRAC_PI_2).rem_euclid(PI);
+    if !(FRAC_PI_8..PI - FRAC_PI_8).contains(&angle) {
+        '-'
+    } else if angle < FRAC_PI_4 + FRAC_PI_8 {
+        '\\'
+    } else if angle < FRAC_PI_2 + FRAC_PI_8 {
+        '|'
+    } else {
+        '/'
+    }
+}
+
+impl CharMap for Edges {
+    fn get_char(&self, image: &SubImage<&GrayImage>) -> char {
+        self.ramp.get_char(image)
+    }
+
+    fn get_subpixels(&self) -> (u32, u32) {
+        (1, 1)
+    }
+
+    fn needs_gradient(&self) -> bool {
+        true
+    }
+
+    fn get_char_with_gradient(&self, image: &SubImage<&GrayImage>, gradient: (f32, f32)) -> char {
+        let (magnitude, angle) = gradient;
+        let effective_threshold = if self.blend { self.threshold * 2.0 } else { self.threshold };
+
+        if magnitude > effective_threshold {
+            direction_char(angle)
+        } else {
+            self.get_char(image)
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CharMaps {
     Simple(Vec<char>),
     Braille,
     Mosaic,
     TeletextMosaic,
+    Edges(Edges),
 }
 
 impl CharMap for CharMaps {
@@ -189,6 +301,7 @@ impl CharMap for CharMaps {
             CharMaps::Braille => Braille.get_char(image),
             CharMaps::Mosaic => Mosaic.get_char(image),
             CharMaps::TeletextMosaic => TeletextMosaic.get_char(image),
+            CharMaps::Edges(edges) => edges.get_char(image),
         }
     }
 
@@ -198,6 +311,7 @@ impl CharMap for CharMaps {
             CharMaps::Braille => Braille.get_subpixels(),
             CharMaps::Mosaic => Mosaic.get_subpixels(),
             CharMaps::TeletextMosaic => TeletextMosaic.get_subpixels(),
+            CharMaps::Edges(edges) => edges.get_subpixels(),
         }
     }
 
@@ -207,6 +321,37 @@ impl CharMap for CharMaps {
             _ => ""
         }
     }
+
+    fn needs_gradient(&self) -> bool {
+        match self {
+            CharMaps::Edges(edges) => edges.needs_gradient(),
+            _ => false,
+        }
+    }
+
+    fn get_char_with_gradient(&self, image: &SubImage<&GrayImage>, gradient: (f32, f32)) -> char {
+        match self {
+            CharMaps::Edges(edges) => edges.get_char_with_gradient(image, gradient),
+            _ => self.get_char(image),
+        }
+    }
+
+    fn is_bilevel(&self) -> bool {
+        match self {
+            CharMaps::Braille => Braille.is_bilevel(),
+            CharMaps::Mosaic => Mosaic.is_bilevel(),
+            CharMaps::TeletextMosaic => TeletextMosaic.is_bilevel(),
+            _ => false,
+        }
+    }
+
+    fn is_blank(&self, c: char) -> bool {
+        match self {
+            CharMaps::Braille => Braille.is_blank(c),
+            CharMaps::TeletextMosaic => TeletextMosaic.is_blank(c),
+            _ => c == ' ',
+        }
+    }
 }
 
 impl<T> From<T> for CharMaps
@@ -215,3 +360,61 @@ impl<T> From<T> for CharMaps
         CharMaps::Simple(value.as_ref().chars().collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_char_with_gradient_matches_edge_orientation() {
+        let image = GrayImage::from_pixel(1, 1, image::Luma([128]));
+        let view = image.view(0, 0, 1, 1);
+        let edges = Edges { ramp: vec![' '], threshold: 10.0, blend: false };
+
+        // Gx large, Gy ~ 0: a vertical edge, whose gradient points horizontally (theta ~ 0).
+        assert_eq!(edges.get_char_with_gradient(&view, (50.0, 0.0)), '|');
+
+        // Gy large, Gx ~ 0: a horizontal edge, whose gradient points vertically (theta ~ pi/2).
+        assert_eq!(
+            edges.get_char_with_gradient(&view, (50.0, std::f32::consts::FRAC_PI_2)),
+            '-'
+        );
+    }
+
+    #[test]
+    fn test_edges_falls_back_to_ramp_below_threshold() {
+        let image = GrayImage::from_pixel(1, 1, image::Luma([200]));
+        let view = image.view(0, 0, 1, 1);
+        let edges = Edges { ramp: vec!['.', '#'], threshold: 10.0, blend: false };
+
+        assert_eq!(
+            edges.get_char_with_gradient(&view, (5.0, 0.0)),
+            edges.get_char(&view)
+        );
+    }
+
+    #[test]
+    fn test_edges_blend_doubles_the_threshold() {
+        let image = GrayImage::from_pixel(1, 1, image::Luma([128]));
+        let view = image.view(0, 0, 1, 1);
+        let edges = Edges { ramp: vec![' '], threshold: 10.0, blend: true };
+
+        // Clears the unblended threshold but not its doubled, blend-mode counterpart: still ramp.
+        assert_eq!(
+            edges.get_char_with_gradient(&view, (15.0, 0.0)),
+            edges.get_char(&view)
+        );
+
+        // Clears the doubled threshold: now drawn as a directional glyph.
+        assert_eq!(edges.get_char_with_gradient(&view, (25.0, 0.0)), '|');
+    }
+
+    #[test]
+    fn test_char_maps_edges_dispatches_to_edges_impl() {
+        let image = GrayImage::from_pixel(1, 1, image::Luma([128]));
+        let view = image.view(0, 0, 1, 1);
+        let char_map = CharMaps::Edges(Edges { ramp: vec![' '], threshold: 10.0, blend: false });
+
+        assert_eq!(char_map.get_char_with_gradient(&view, (50.0, 0.0)), '|');
+    }
+}
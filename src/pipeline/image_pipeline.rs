@@ -18,6 +18,13 @@ pub struct ImagePipeline<T: CharMap> {
     pub char_map: T,
     /// Whether to add newlines to the output at the end of each line
     pub new_lines: bool,
+    /// Whether to wrap each non-blank character in a 24-bit ANSI foreground escape taken from
+    /// the corresponding pixel of the resized color image.
+    pub color: bool,
+    /// Whether to run a Floyd-Steinberg error-diffusion pass over the grayscale subpixel image
+    /// before thresholding. Only takes effect for 1-bit-per-dot char maps (see
+    /// `CharMap::is_bilevel`); ramp-based char maps ignore this.
+    pub dither: bool,
 }
 
 impl<T: CharMap> ImagePipeline<T> {
@@ -30,11 +37,24 @@ impl<T: CharMap> ImagePipeline<T> {
     ///   height.
     /// * `char_map` - A vector of characters to be used as the lookup table for ASCII
     ///   conversion.
-    pub fn new(target_resolution: (u32, u32), char_map: T, new_lines: bool) -> Self {
+    /// * `new_lines` - Whether to add newlines to the output at the end of each line.
+    /// * `color` - Whether to colorize each character with the matching pixel of the resized
+    ///   color image.
+    /// * `dither` - Whether to Floyd-Steinberg dither the grayscale image before thresholding,
+    ///   for char maps that support it (see `CharMap::is_bilevel`).
+    pub fn new(
+        target_resolution: (u32, u32),
+        char_map: T,
+        new_lines: bool,
+        color: bool,
+        dither: bool,
+    ) -> Self {
         Self {
             target_resolution,
             char_map,
             new_lines,
+            color,
+            dither,
         }
     }
 
@@ -95,7 +115,12 @@ impl<T: CharMap> ImagePipeline<T> {
             fr::ResizeAlg::Convolution(fr::FilterType::Box)
         )?;
 
-        Ok((subpixel_img.into_luma8(), color_img.into_rgb8()))
+        let mut subpixel_img = subpixel_img.into_luma8();
+        if self.dither && self.char_map.is_bilevel() {
+            floyd_steinberg_dither(&mut subpixel_img);
+        }
+
+        Ok((subpixel_img, color_img.into_rgb8()))
     }
 
     fn resize_single(&self, img: &DynamicImage, width: NonZeroU32, height: NonZeroU32, algo: fr::ResizeAlg) -> Result<DynamicImage, MyError> {
@@ -133,7 +158,8 @@ impl<T: CharMap> ImagePipeline<T> {
     }
 
     /// Converts the given grayscale image to ASCII art using the character lookup table stored in
-    /// this `ImagePipeline`.
+    /// this `ImagePipeline`, optionally colorizing each character with the matching pixel of
+    /// `color_img` (see the `color` field).
     ///
     /// This method iterates through the pixels of the input image and maps each pixel's grayscale
     /// value to a character from the lookup table. The resulting ASCII art is returned as a
@@ -142,11 +168,15 @@ impl<T: CharMap> ImagePipeline<T> {
     /// # Arguments
     ///
     /// * `input` - A reference to a `GrayImage` to be converted to ASCII art.
+    /// * `color_img` - A reference to the `RgbImage` (at `target_resolution`) to source colors
+    ///   from when `self.color` is set.
     ///
     /// # Returns
     ///
     /// A `String` containing the ASCII art representation of the input image.
-    pub fn to_ascii(&self, input: &GrayImage) -> Vec<String> {
+    pub fn to_ascii(&self, input: &GrayImage, color_img: &RgbImage) -> Vec<String> {
+        const COLOR_RESET: &str = "\x1b[0m";
+
         let (width, height) = self.target_resolution;
 
         let mut output = Vec::with_capacity(height as usize);
@@ -154,19 +184,52 @@ impl<T: CharMap> ImagePipeline<T> {
         let (subpixel_width, subpixel_height) = self.char_map.get_subpixels();
         assert_eq!(width * subpixel_width, input.width());
         assert_eq!(height * subpixel_height, input.height());
+        if self.color {
+            assert_eq!(width, color_img.width());
+            assert_eq!(height, color_img.height());
+        }
+
+        let needs_gradient = self.char_map.needs_gradient();
 
         for y in 0..height {
-            let line = (0..width).map(|x| {
-                self.char_map.get_char(&input.view(x * subpixel_width, y * subpixel_height, subpixel_width, subpixel_height))
-            })
+            let mut line = String::new();
+
+            for x in 0..width {
+                let view = input.view(
+                    x * subpixel_width,
+                    y * subpixel_height,
+                    subpixel_width,
+                    subpixel_height,
+                );
+
+                let c = if needs_gradient {
+                    let gradient =
+                        sobel_gradient(input, (x * subpixel_width) as i64, (y * subpixel_height) as i64);
+                    self.char_map.get_char_with_gradient(&view, gradient)
+                } else {
+                    self.char_map.get_char(&view)
+                };
+
+                if self.color && !self.char_map.is_blank(c) {
+                    let [r, g, b] = color_img.get_pixel(x, y).0;
+                    line.push_str(&format!("\x1b[38;2;{r};{g};{b}m"));
+                }
+
+                line.push(c);
+            }
+
+            if self.color {
+                line.push_str(COLOR_RESET);
+            }
+
             // Add newlines to the end of each row except the last. NOTE: these
             // are not really needed because the terminal will wrap lines. But
             // if you want to copy the output to a file it would be a single
             // long string without them.
-            .chain(
-                ['\n', '\r'].into_iter().take(if self.new_lines && y < height - 1 { 2 } else { 0 })
-            )
-            .collect();
+            if self.new_lines && y < height - 1 {
+                line.push('\n');
+                line.push('\r');
+            }
 
             output.push(line);
         }
@@ -175,6 +238,72 @@ impl<T: CharMap> ImagePipeline<T> {
     }
 }
 
+/// Floyd-Steinberg dithers `image` in place: each pixel is hard-thresholded to black or white,
+/// and the resulting quantization error is diffused to its right, bottom-left, bottom, and
+/// bottom-right neighbors (weighted 7/16, 3/16, 5/16, and 1/16), skipping neighbors that fall
+/// outside the image. This trades banding on gradients for noise, which reads much better once
+/// thresholded down to one bit per dot by char maps like `Braille` and `Mosaic`.
+fn floyd_steinberg_dither(image: &mut GrayImage) {
+    const NEIGHBORS: [(i64, i64, i16); 4] = [(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)];
+
+    let (width, height) = image.dimensions();
+    let mut buffer: Vec<i16> = image.pixels().map(|p| p[0] as i16).collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) as usize;
+            let old = buffer[i];
+            let new = if old < 128 { 0 } else { 255 };
+            let err = old - new;
+            buffer[i] = new;
+
+            for (dx, dy, fraction) in NEIGHBORS {
+                let nx = x as i64 + dx;
+                let ny = y as i64 + dy;
+                if nx < 0 || ny < 0 || nx as u32 >= width || ny as u32 >= height {
+                    continue;
+                }
+
+                let ni = (ny as u32 * width + nx as u32) as usize;
+                buffer[ni] = (buffer[ni] + err * fraction / 16).clamp(0, 255);
+            }
+        }
+    }
+
+    for (pixel, value) in image.pixels_mut().zip(buffer) {
+        pixel[0] = value as u8;
+    }
+}
+
+/// Computes the Sobel gradient `(magnitude, angle)` of `image` at `(x, y)`, sampling the 3x3
+/// neighborhood with the standard horizontal/vertical kernels. Out-of-range neighbors (at the
+/// image borders) are clamped to the nearest in-range pixel rather than skipped.
+fn sobel_gradient(image: &GrayImage, x: i64, y: i64) -> (f32, f32) {
+    const KX: [[i32; 3]; 3] = [[-1, 0, 1], [-2, 0, 2], [-1, 0, 1]];
+    const KY: [[i32; 3]; 3] = [[-1, -2, -1], [0, 0, 0], [1, 2, 1]];
+
+    let (width, height) = image.dimensions();
+    let sample = |dx: i64, dy: i64| -> i32 {
+        let sx = (x + dx).clamp(0, width as i64 - 1) as u32;
+        let sy = (y + dy).clamp(0, height as i64 - 1) as u32;
+        image.get_pixel(sx, sy)[0] as i32
+    };
+
+    let mut gx = 0;
+    let mut gy = 0;
+    for ky in -1..=1i64 {
+        for kx in -1..=1i64 {
+            let p = sample(kx, ky);
+            gx += p * KX[(ky + 1) as usize][(kx + 1) as usize];
+            gy += p * KY[(ky + 1) as usize][(kx + 1) as usize];
+        }
+    }
+
+    let magnitude = ((gx * gx + gy * gy) as f32).sqrt();
+    let angle = (gy as f32).atan2(gx as f32);
+    (magnitude, angle)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,14 +326,14 @@ mod tests {
 
     #[test]
     fn test_new() {
-        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false);
+        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false, false, false);
         assert_eq!(image.target_resolution, (120, 80));
         assert_eq!(image.char_map, vec!['a', 'b', 'c']);
     }
 
     #[test]
     fn test_process() {
-        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false);
+        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false, false, false);
         let input = download_image(TEST_IMAGE_URL).expect("Failed to download image");
 
         let output = image.resize(&input).expect("Failed to resize image").1;
@@ -214,27 +343,86 @@ mod tests {
 
     #[test]
     fn test_to_ascii_ext() {
-        let image = ImagePipeline::new((120, 80), CHARS1.chars().collect::<Vec<char>>(), false);
+        let image = ImagePipeline::new((120, 80), CHARS1.chars().collect::<Vec<char>>(), false, false, false);
         let input = download_image(TEST_IMAGE_URL).expect("Failed to download image");
-        let output = image.to_ascii(
-            &image
-                .resize(&input)
-                .expect("Failed to resize image")
-                .0,
-        );
+        let (gray, color) = image.resize(&input).expect("Failed to resize image");
+        let output = image.to_ascii(&gray, &color);
         assert_eq!(output.iter().map(|str| str.chars().count()).sum::<usize>(), 120 * 80);
     }
 
     #[test]
     fn test_to_ascii() {
-        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false);
+        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false, false, false);
         let input = download_image(TEST_IMAGE_URL).expect("Failed to download image");
-        let output = image.to_ascii(
-            &image
-                .resize(&input)
-                .expect("Failed to resize image")
-                .0,
-        );
+        let (gray, color) = image.resize(&input).expect("Failed to resize image");
+        let output = image.to_ascii(&gray, &color);
         assert_eq!(output.iter().map(|str| str.chars().count()).sum::<usize>(), 120 * 80);
     }
+
+    #[test]
+    fn test_to_ascii_color() {
+        let image = ImagePipeline::new((120, 80), vec!['a', 'b', 'c'], false, true, false);
+        let input = download_image(TEST_IMAGE_URL).expect("Failed to download image");
+        let (gray, color) = image.resize(&input).expect("Failed to resize image");
+        let output = image.to_ascii(&gray, &color);
+        assert!(output.iter().all(|line| line.contains("\x1b[38;2;")));
+    }
+
+    #[test]
+    fn test_color_skips_escape_for_non_space_blank_glyph() {
+        use crate::pipeline::char_maps::{Braille, CharMap};
+
+        let image = ImagePipeline::new((3, 2), Braille, false, true, false);
+        let (subpixel_width, subpixel_height) = Braille.get_subpixels();
+        let gray = GrayImage::from_pixel(3 * subpixel_width, 2 * subpixel_height, image::Luma([0]));
+        let color = RgbImage::from_pixel(3, 2, image::Rgb([255, 0, 0]));
+
+        let output = image.to_ascii(&gray, &color);
+        assert!(!output.iter().any(|line| line.contains("\x1b[38;2;")));
+    }
+
+    #[test]
+    fn test_sobel_gradient_flat_image_has_no_magnitude() {
+        let image = GrayImage::from_pixel(5, 5, image::Luma([128]));
+        let (magnitude, _) = sobel_gradient(&image, 2, 2);
+        assert_eq!(magnitude, 0.0);
+    }
+
+    #[test]
+    fn test_sobel_gradient_vertical_edge() {
+        let mut image = GrayImage::from_pixel(5, 5, image::Luma([0]));
+        for y in 0..5 {
+            for x in 3..5 {
+                image.put_pixel(x, y, image::Luma([255]));
+            }
+        }
+
+        let (magnitude, _) = sobel_gradient(&image, 2, 2);
+        assert!(magnitude > 0.0);
+    }
+
+    #[test]
+    fn test_floyd_steinberg_dither_is_bilevel() {
+        let mut image = GrayImage::from_fn(8, 8, |x, _| image::Luma([(x * 255 / 7) as u8]));
+        floyd_steinberg_dither(&mut image);
+        assert!(image.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_dither_applies_to_bilevel_char_maps() {
+        use crate::pipeline::char_maps::Braille;
+
+        let image = ImagePipeline::new((2, 2), Braille, false, false, true);
+        let input = download_image(TEST_IMAGE_URL).expect("Failed to download image");
+        let (gray, _) = image.resize(&input).expect("Failed to resize image");
+        assert!(gray.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_dither_does_not_apply_to_ramp_char_maps() {
+        let image = ImagePipeline::new((2, 2), CHARS1.chars().collect::<Vec<char>>(), false, false, true);
+        let input = download_image(TEST_IMAGE_URL).expect("Failed to download image");
+        let (gray, _) = image.resize(&input).expect("Failed to resize image");
+        assert!(gray.pixels().any(|p| p[0] != 0 && p[0] != 255));
+    }
 }